@@ -1,41 +1,91 @@
 use bitreader::BitReader;
-use btleplug::api::{BDAddr, Central, CharPropFlags, Manager as _, Peripheral, ScanFilter};
+use btleplug::api::{BDAddr, Central, CentralEvent, CharPropFlags, Manager as _, Peripheral, ScanFilter};
 use btleplug::platform::Manager;
 use core::{f64, fmt};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::{Duration, Instant};
 use dotenv::dotenv;
-use futures::stream::{self, StreamExt};
-use influxdb2::Client;
+use futures::stream::StreamExt;
 use std::error::Error;
 use uuid::Uuid;
 use serde::Deserialize;
 use serde_json;
 use std::fs;
+use tokio::sync::mpsc;
 use tokio::time::{sleep, sleep_until};
 
+mod output;
+use output::Output;
+
 /// UUID of the characteristic for which we should subscribe to notifications.
 const NOTIFY_CHARACTERISTIC_UUID: Uuid = Uuid::from_u128(0x6e400003_b5a3_f393_e0a9_e50e24dcca9e);
+/// Bluetooth SIG company identifier Ruuvi Innovations broadcasts RAWv2 payloads under.
+const RUUVI_MANUFACTURER_ID: u16 = 0x0499;
+/// How many consecutive poll cycles a connected tag may miss a notification before
+/// it's considered stale and queued for reconnection.
+const MAX_MISSED_CYCLES: u32 = 3;
+const INITIAL_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(60);
+const RECONNECT_ATTEMPT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub(crate) struct RuuviData {
+    pub(crate) name: String,
+    pub(crate) mac_address: BDAddr,
+    pub(crate) temperature: f32,
+    pub(crate) humidity: f32,
+    pub(crate) pressure: u32,
+    pub(crate) acceleration_x: f32,
+    pub(crate) acceleration_y: f32,
+    pub(crate) acceleration_z: f32,
+    pub(crate) voltage: f32,
+    /// Only present for format 5 (RAWv2); format 3 has no tx power field.
+    pub(crate) tx_power: Option<u16>,
+    /// Only present for format 5 (RAWv2); format 3 has no movement counter.
+    pub(crate) movement_counter: Option<u8>,
+    /// Only present for format 5 (RAWv2); format 3 has no measurement sequence.
+    pub(crate) measurement_sequence: Option<u16>,
+    pub(crate) rssi: Option<i16>,
+    pub(crate) received_at: Instant,
+}
 
 #[derive(Debug)]
-struct RuuviData {
-    name: String,
-    mac_address: BDAddr,
-    temperature: f32,
-    humidity: f32,
-    pressure: u32,
-    acceleration_x: f32,
-    acceleration_y: f32,
-    acceleration_z: f32,
-    voltage: f32,
-    tx_power: u16,
-    movement_counter: u8,
-    measurement_sequence: u16,
+struct UnknownFormatError(u8);
+
+impl fmt::Display for UnknownFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unsupported Ruuvi data format byte: 0x{:02x}", self.0)
+    }
 }
 
+impl Error for UnknownFormatError {}
+
 impl RuuviData {
-    fn new(name: String, mac_address: BDAddr, raw_data: Vec<u8>) -> Result<RuuviData, Box<dyn Error>> {
-        let mut reader = BitReader::new(&raw_data);
+    fn new(
+        name: String,
+        mac_address: BDAddr,
+        raw_data: Vec<u8>,
+        rssi: Option<i16>,
+    ) -> Result<RuuviData, Box<dyn Error>> {
+        let format = *raw_data.first().ok_or("empty Ruuvi advertisement payload")?;
+        let received_at = Instant::now();
+        match format {
+            5 => Self::parse_format5(name, mac_address, &raw_data, rssi, received_at),
+            3 => Self::parse_format3(name, mac_address, &raw_data, rssi, received_at),
+            other => Err(Box::new(UnknownFormatError(other))),
+        }
+    }
+
+    /// RAWv2: temp i16*0.005, humidity u16*0.0025, pressure u16+50000, accel i16/1000,
+    /// 11-bit voltage*0.001+1.6, 5-bit tx_power*2-40, movement u8, sequence u16.
+    fn parse_format5(
+        name: String,
+        mac_address: BDAddr,
+        raw_data: &[u8],
+        rssi: Option<i16>,
+        received_at: Instant,
+    ) -> Result<RuuviData, Box<dyn Error>> {
+        let mut reader = BitReader::new(raw_data);
         reader.skip(8).unwrap();
 
         let temperature = reader.read_u16(16)? as f32 * 0.005;
@@ -59,9 +109,59 @@ impl RuuviData {
             acceleration_y,
             acceleration_z,
             voltage,
-            tx_power,
-            movement_counter,
-            measurement_sequence,
+            tx_power: Some(tx_power),
+            movement_counter: Some(movement_counter),
+            measurement_sequence: Some(measurement_sequence),
+            rssi,
+            received_at,
+        })
+    }
+
+    /// RAWv1: humidity u8*0.5 %RH, temperature as sign+integer byte plus a 0.01 degC
+    /// fraction byte, pressure u16+50000, accel i16 milli-g, voltage u16 mV. No
+    /// tx_power/movement/sequence in this format.
+    fn parse_format3(
+        name: String,
+        mac_address: BDAddr,
+        raw_data: &[u8],
+        rssi: Option<i16>,
+        received_at: Instant,
+    ) -> Result<RuuviData, Box<dyn Error>> {
+        let mut reader = BitReader::new(raw_data);
+        reader.skip(8).unwrap();
+
+        let humidity = reader.read_u8(8)? as f32 * 0.5;
+
+        let temperature_is_negative = reader.read_u8(1)? == 1;
+        let temperature_integer = reader.read_u8(7)? as f32;
+        let temperature_fraction = reader.read_u8(8)? as f32 * 0.01;
+        let temperature = if temperature_is_negative {
+            -(temperature_integer + temperature_fraction)
+        } else {
+            temperature_integer + temperature_fraction
+        };
+
+        let pressure = reader.read_u16(16)? as u32 + 50_000;
+        let acceleration_x = reader.read_i16(16)? as f32 / 1000.0;
+        let acceleration_y = reader.read_i16(16)? as f32 / 1000.0;
+        let acceleration_z = reader.read_i16(16)? as f32 / 1000.0;
+        let voltage = reader.read_u16(16)? as f32 / 1000.0;
+
+        Ok(RuuviData {
+            name,
+            mac_address,
+            temperature,
+            humidity,
+            pressure,
+            acceleration_x,
+            acceleration_y,
+            acceleration_z,
+            voltage,
+            tx_power: None,
+            movement_counter: None,
+            measurement_sequence: None,
+            rssi,
+            received_at,
         })
     }
 }
@@ -69,49 +169,123 @@ impl fmt::Display for RuuviData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "Mac: {}, temp: {}, humidity: {}, measurement sequence: {}",
+            "Mac: {}, temp: {}, humidity: {}, measurement sequence: {:?}",
             self.mac_address, self.temperature, self.humidity, self.measurement_sequence
         )
     }
 }
 
+impl serde::Serialize for RuuviData {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        // `received_at` is a monotonic Instant with no wall-clock meaning, so it's left
+        // out here; it's only used in-process to gauge how stale a point is.
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("RuuviData", 13)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("mac_address", &self.mac_address.to_string())?;
+        state.serialize_field("temperature", &self.temperature)?;
+        state.serialize_field("humidity", &self.humidity)?;
+        state.serialize_field("pressure", &self.pressure)?;
+        state.serialize_field("acceleration_x", &self.acceleration_x)?;
+        state.serialize_field("acceleration_y", &self.acceleration_y)?;
+        state.serialize_field("acceleration_z", &self.acceleration_z)?;
+        state.serialize_field("voltage", &self.voltage)?;
+        state.serialize_field("tx_power", &self.tx_power)?;
+        state.serialize_field("movement_counter", &self.movement_counter)?;
+        state.serialize_field("measurement_sequence", &self.measurement_sequence)?;
+        state.serialize_field("rssi", &self.rssi)?;
+        state.end()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TagHealth {
+    missed_cycles: u32,
+    backoff: Duration,
+}
+
+impl Default for TagHealth {
+    fn default() -> Self {
+        TagHealth {
+            missed_cycles: 0,
+            backoff: INITIAL_RECONNECT_BACKOFF,
+        }
+    }
+}
+
+enum ReconnectOutcome {
+    Success(String, btleplug::platform::Peripheral),
+    Failure(String, btleplug::platform::Peripheral, Duration),
+}
+
 #[derive(Deserialize)]
 struct Config {
-    influx_bucket: String,
-    influx_measurement: String,
-    influx_host: String,
-    influx_org: String,
-    influx_token: String,
+    outputs: Vec<output::OutputConfig>,
     tags: HashMap<String, String>,
     delay_in_secs: u32,
+    /// When true, never connect to tags and instead read RAWv2 payloads straight out of
+    /// their advertisement manufacturer data.
+    #[serde(default)]
+    passive: bool,
 }
 
 struct RustSniffer {
-    influx_client: Client,
-    influx_bucket: String,
-    influx_measurement: String,
+    outputs: Vec<Box<dyn Output>>,
     tag_names: HashMap<String, BDAddr>,
     ruuvis: HashMap<String, btleplug::platform::Peripheral>,
+    tag_health: HashMap<String, TagHealth>,
+    reconnecting: HashSet<String>,
+    reconnect_tx: mpsc::UnboundedSender<ReconnectOutcome>,
+    reconnect_rx: mpsc::UnboundedReceiver<ReconnectOutcome>,
     delay: u32,
+    passive: bool,
 }
 
 impl RustSniffer {
-    async fn new(config: Config) -> RustSniffer {
+    async fn new(config: Config) -> Result<RustSniffer, Box<dyn Error>> {
         let mut tag_names: HashMap<String, BDAddr> = HashMap::new();
 
         for (name, mac) in config.tags {
             tag_names.insert(name, BDAddr::from_str_delim(&mac).unwrap());
         };
 
+        let outputs = output::build_outputs(config.outputs).await?;
+        let (reconnect_tx, reconnect_rx) = mpsc::unbounded_channel();
 
-        RustSniffer {
-            influx_client: Client::new(config.influx_host, config.influx_org, config.influx_token),
-            influx_bucket: config.influx_bucket,
-            influx_measurement: config.influx_measurement,
+        Ok(RustSniffer {
+            outputs,
             ruuvis: HashMap::new(),
+            tag_health: HashMap::new(),
+            reconnecting: HashSet::new(),
+            reconnect_tx,
+            reconnect_rx,
             tag_names,
             delay: config.delay_in_secs,
+            passive: config.passive,
+        })
+    }
+
+    async fn register_if_wanted(
+        &mut self,
+        peripheral: &btleplug::platform::Peripheral,
+    ) -> Result<(), Box<dyn Error>> {
+        let address = peripheral.address();
+        let matched_name = self
+            .tag_names
+            .iter()
+            .find(|(_, mac)| **mac == address)
+            .map(|(name, _)| name.clone());
+
+        if let Some(name) = matched_name {
+            if !self.ruuvis.contains_key(&name) {
+                println!("found {}", address);
+                self.ruuvis.insert(name, peripheral.clone());
+            }
         }
+        Ok(())
     }
 
     async fn discover(&mut self) -> Result<(), Box<dyn Error>>{
@@ -124,45 +298,35 @@ impl RustSniffer {
 
         for adapter in adapter_list.iter() {
             println!("Starting scan...");
+            let mut events = adapter.events().await?;
             adapter
                 .start_scan(ScanFilter::default())
                 .await
                 .expect("Can't scan BLE adapter for connected devices...");
 
-            loop {
-                if self.tag_names.len() == self.ruuvis.len() {
+            while self.tag_names.len() != self.ruuvis.len() {
+                let Some(event) = events.next().await else {
                     break;
-                }
-                sleep(Duration::from_secs(1)).await;
-                let peripherals = adapter_list[0].peripherals().await?;
-
-                for (name, mac) in &self.tag_names {
-                    for peripheral in &peripherals {
-                        if peripheral.address() == *mac {
-                            if !self.ruuvis
-                                .iter()
-                                .any(|ruuvi| ruuvi.1.address() == peripheral.address())
-                            {
-                                println!("found {}", mac);
-                                self.ruuvis.insert(name.to_string(), peripheral.clone());
-                            }
-                        }
+                };
+                match event {
+                    CentralEvent::DeviceDiscovered(id)
+                    | CentralEvent::DeviceUpdated(id)
+                    | CentralEvent::ManufacturerDataAdvertisement { id, .. } => {
+                        let peripheral = adapter.peripheral(&id).await?;
+                        self.register_if_wanted(&peripheral).await?;
                     }
+                    _ => {}
                 }
             }
         }
+        if self.passive {
+            println!("Passive mode: skipping connect/subscribe, reading advertisements only.");
+            return Ok(());
+        }
+
         println!("Connecting and subscribing..");
         for (_name, ruuvi) in self.ruuvis.iter() {
-            ruuvi.connect().await?;
-            ruuvi.discover_services().await?;
-            for characteristic in ruuvi.characteristics() {
-                if characteristic.uuid == NOTIFY_CHARACTERISTIC_UUID
-                    && characteristic.properties.contains(CharPropFlags::NOTIFY)
-                {
-                    ruuvi.subscribe(&characteristic).await?;
-                    break;
-                }
-            }
+            Self::connect_and_subscribe(ruuvi).await?;
             println!(
                 "Ruuvi {} connected: {}",
                 ruuvi.address(),
@@ -172,6 +336,124 @@ impl RustSniffer {
         Ok(())
     }
 
+    /// Connects to a tag and subscribes to its Nordic UART notify characteristic.
+    /// Shared by initial discovery and the reconnection supervisor.
+    async fn connect_and_subscribe(
+        ruuvi: &btleplug::platform::Peripheral,
+    ) -> Result<(), Box<dyn Error>> {
+        ruuvi.connect().await?;
+        ruuvi.discover_services().await?;
+        for characteristic in ruuvi.characteristics() {
+            if characteristic.uuid == NOTIFY_CHARACTERISTIC_UUID
+                && characteristic.properties.contains(CharPropFlags::NOTIFY)
+            {
+                ruuvi.subscribe(&characteristic).await?;
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // No-op in passive mode, since there's no connection to supervise.
+    async fn supervise_connections(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.passive {
+            return Ok(());
+        }
+
+        while let Ok(outcome) = self.reconnect_rx.try_recv() {
+            match outcome {
+                ReconnectOutcome::Success(name, ruuvi) => {
+                    println!("Reconnected to {}", name);
+                    self.ruuvis.insert(name.clone(), ruuvi);
+                    self.tag_health.insert(name.clone(), TagHealth::default());
+                    self.reconnecting.remove(&name);
+                }
+                ReconnectOutcome::Failure(name, ruuvi, next_backoff) => {
+                    self.tag_health.entry(name.clone()).or_default().backoff = next_backoff;
+                    self.reconnecting.remove(&name);
+                    self.spawn_reconnect(name, ruuvi);
+                }
+            }
+        }
+
+        let mut stale = Vec::new();
+        for (name, ruuvi) in self.ruuvis.iter() {
+            if self.reconnecting.contains(name) {
+                continue;
+            }
+            let missed_too_many = self
+                .tag_health
+                .get(name)
+                .map_or(false, |health| health.missed_cycles >= MAX_MISSED_CYCLES);
+            let connected = match ruuvi.is_connected().await {
+                Ok(connected) => connected,
+                Err(err) => {
+                    eprintln!("Failed to check connection state for {}: {}", name, err);
+                    false
+                }
+            };
+            if missed_too_many || !connected {
+                stale.push(name.clone());
+            }
+        }
+
+        for name in stale {
+            if let Some(ruuvi) = self.ruuvis.remove(&name) {
+                self.spawn_reconnect(name, ruuvi);
+            }
+        }
+        Ok(())
+    }
+
+    fn spawn_reconnect(&mut self, name: String, ruuvi: btleplug::platform::Peripheral) {
+        let backoff = self.tag_health.entry(name.clone()).or_default().backoff;
+        self.reconnecting.insert(name.clone());
+        let tx = self.reconnect_tx.clone();
+
+        tokio::spawn(async move {
+            eprintln!("Tag {} unhealthy, retrying in {:?}...", name, backoff);
+            sleep(backoff).await;
+
+            let next_backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+            match tokio::time::timeout(
+                RECONNECT_ATTEMPT_TIMEOUT,
+                RustSniffer::connect_and_subscribe(&ruuvi),
+            )
+            .await
+            {
+                Ok(Ok(())) => {
+                    let _ = tx.send(ReconnectOutcome::Success(name, ruuvi));
+                }
+                Ok(Err(err)) => {
+                    eprintln!("Reconnect to {} failed: {}", name, err);
+                    let _ = tx.send(ReconnectOutcome::Failure(name, ruuvi, next_backoff));
+                }
+                Err(_) => {
+                    eprintln!("Reconnect to {} timed out after {:?}", name, RECONNECT_ATTEMPT_TIMEOUT);
+                    let _ = tx.send(ReconnectOutcome::Failure(name, ruuvi, next_backoff));
+                }
+            }
+        });
+    }
+
+    async fn read_passive(
+        name: &str,
+        ruuvi: &btleplug::platform::Peripheral,
+    ) -> Result<Option<RuuviData>, Box<dyn Error>> {
+        let Some(properties) = ruuvi.properties().await? else {
+            return Ok(None);
+        };
+        let Some(raw_data) = properties.manufacturer_data.get(&RUUVI_MANUFACTURER_ID) else {
+            return Ok(None);
+        };
+        Ok(Some(RuuviData::new(
+            name.to_string(),
+            ruuvi.address(),
+            raw_data.clone(),
+            properties.rssi,
+        )?))
+    }
+
     async fn start(&mut self) -> Result<(), Box<dyn Error>> {
         println!("Starting, trying to connect to all tags...");
         loop {
@@ -187,52 +469,72 @@ impl RustSniffer {
         loop {
             let continue_time = Instant::now() + Duration::from_secs(self.delay.into());
             self.update_data().await?;
+            self.supervise_connections().await?;
             sleep_until(continue_time.into()).await;
         }
     }
 
     async fn update_data(
-        &self
+        &mut self
     ) -> Result<(), Box<dyn Error>> {
         println!("Reading data...");
         let mut ruuvi_datas: Vec<RuuviData> = Vec::new();
         for (name, ruuvi) in self.ruuvis.iter() {
+            if self.passive {
+                match Self::read_passive(name, ruuvi).await {
+                    Ok(Some(data)) => ruuvi_datas.push(data),
+                    Ok(None) => {}
+                    Err(err) => eprintln!("Failed to read {} passively: {}", name, err),
+                }
+                continue;
+            }
+
+            let mut got_notification = false;
             if let Ok(mut notification) = ruuvi.notifications().await {
                 if let Some(data) = notification.next().await {
-                    ruuvi_datas.push(RuuviData::new(name.to_string(), ruuvi.address(), data.value.clone()).unwrap());
+                    let rssi = match ruuvi.properties().await {
+                        Ok(properties) => properties.and_then(|properties| properties.rssi),
+                        Err(err) => {
+                            eprintln!("Failed to read properties for {}: {}", name, err);
+                            None
+                        }
+                    };
+                    match RuuviData::new(name.to_string(), ruuvi.address(), data.value.clone(), rssi) {
+                        Ok(data) => {
+                            ruuvi_datas.push(data);
+                            got_notification = true;
+                        }
+                        Err(err) => eprintln!("Failed to parse data from {}: {}", name, err),
+                    }
                 }
             }
+
+            let health = self.tag_health.entry(name.clone()).or_default();
+            if got_notification {
+                health.missed_cycles = 0;
+            } else {
+                health.missed_cycles += 1;
+            }
         }
         self.send_data(ruuvi_datas).await?;
         Ok(())
     }
 
     async fn send_data(&self, ruuvi_datas: Vec<RuuviData>) -> Result<(), Box<dyn Error>> {
-        use influxdb2::models::DataPoint;
-
-        let mut points: Vec<DataPoint> = Vec::new();
-        for data in ruuvi_datas {
-            points.push(
-                DataPoint::builder(self.influx_measurement.clone())
-                    .tag("name", data.name)
-                    .tag("mac", data.mac_address.to_string())
-                    .field("temperature", data.temperature as f64)
-                    .field("humidity", data.humidity as f64)
-                    .field("pressure", data.pressure as i64)
-                    .field("acceleration_x", data.acceleration_x as f64)
-                    .field("acceleration_y", data.acceleration_y as f64)
-                    .field("acceleration_z", data.acceleration_z as f64)
-                    .field("voltage", data.voltage as f64)
-                    .field("tx_power", data.tx_power as i64)
-                    .field("movement_counter", data.movement_counter as i64)
-                    .field("measurement_sequence", data.measurement_sequence as i64)
-                    .build()?,
-            );
+        println!(
+            "Sending data ({} points) to {} output(s)...",
+            ruuvi_datas.len(),
+            self.outputs.len()
+        );
+        let results = futures::future::join_all(
+            self.outputs.iter().map(|output| output.write(&ruuvi_datas)),
+        )
+        .await;
+        for result in results {
+            if let Err(err) = result {
+                eprintln!("Output failed: {}", err);
+            }
         }
-        println!("Sending data ({} points)...", points.len());
-        self.influx_client
-            .write(&self.influx_bucket, stream::iter(points))
-            .await?;
         Ok(())
     }
 }
@@ -241,9 +543,71 @@ impl RustSniffer {
 async fn main() -> Result<(), Box<dyn Error>> {
     pretty_env_logger::init();
     dotenv().ok();
-    
-    let config: Config = serde_json::from_str(&fs::read_to_string("config.json").unwrap()).unwrap(); 
 
-    let mut rs = RustSniffer::new(config).await;
+    let config: Config = serde_json::from_str(&fs::read_to_string("config.json").unwrap()).unwrap();
+
+    let mut rs = RustSniffer::new(config).await?;
     rs.start().await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mac() -> BDAddr {
+        BDAddr::from_str_delim("11:22:33:44:55:66").unwrap()
+    }
+
+    // Official Ruuvi data format 5 (RAWv2) test vector, see the Ruuvi sensor protocol spec.
+    const FORMAT5_SAMPLE: [u8; 18] = [
+        0x05, 0x12, 0xFC, 0x53, 0x94, 0xC3, 0x7C, 0x00, 0x04, 0xFF, 0xFC, 0x04, 0x0C, 0xAC, 0x36,
+        0x42, 0x00, 0xCD,
+    ];
+
+    #[test]
+    fn parses_format5_sample() {
+        let data = RuuviData::new("tag".to_string(), mac(), FORMAT5_SAMPLE.to_vec(), Some(-70))
+            .unwrap();
+
+        assert!((data.temperature - 24.3).abs() < 0.001);
+        assert!((data.humidity - 53.49).abs() < 0.001);
+        assert_eq!(data.pressure, 100_044);
+        assert!((data.acceleration_x - 0.004).abs() < 0.001);
+        assert!((data.acceleration_y - -0.004).abs() < 0.001);
+        assert!((data.acceleration_z - 1.036).abs() < 0.001);
+        assert!((data.voltage - 2.977).abs() < 0.001);
+        assert_eq!(data.tx_power, Some(4));
+        assert_eq!(data.movement_counter, Some(66));
+        assert_eq!(data.measurement_sequence, Some(205));
+        assert_eq!(data.rssi, Some(-70));
+    }
+
+    // Format 3 (RAWv1): humidity 25.0 %RH, temperature +22.50 C, pressure 50000 Pa,
+    // acceleration all zero, battery voltage 3.100 V.
+    const FORMAT3_SAMPLE: [u8; 14] = [
+        0x03, 0x32, 0x16, 0x32, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x1C,
+    ];
+
+    #[test]
+    fn parses_format3_sample() {
+        let data =
+            RuuviData::new("tag".to_string(), mac(), FORMAT3_SAMPLE.to_vec(), None).unwrap();
+
+        assert!((data.temperature - 22.5).abs() < 0.001);
+        assert!((data.humidity - 25.0).abs() < 0.001);
+        assert_eq!(data.pressure, 50_000);
+        assert!((data.acceleration_x - 0.0).abs() < 0.001);
+        assert!((data.acceleration_y - 0.0).abs() < 0.001);
+        assert!((data.acceleration_z - 0.0).abs() < 0.001);
+        assert!((data.voltage - 3.1).abs() < 0.001);
+        assert_eq!(data.tx_power, None);
+        assert_eq!(data.movement_counter, None);
+        assert_eq!(data.measurement_sequence, None);
+    }
+
+    #[test]
+    fn rejects_unknown_format_byte() {
+        let err = RuuviData::new("tag".to_string(), mac(), vec![0xFF; 18], None).unwrap_err();
+        assert!(err.to_string().contains("0xff"));
+    }
+}
@@ -0,0 +1,201 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::error::Error;
+
+use crate::RuuviData;
+
+#[async_trait]
+pub(crate) trait Output: Send + Sync {
+    async fn write(&self, data: &[RuuviData]) -> Result<(), Box<dyn Error>>;
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum OutputConfig {
+    Influxdb {
+        bucket: String,
+        measurement: String,
+        host: String,
+        org: String,
+        token: String,
+    },
+    Mqtt {
+        host: String,
+        port: u16,
+        topic: String,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+    },
+    Json {
+        /// Path to append newline-delimited JSON to. Writes to stdout when omitted.
+        #[serde(default)]
+        path: Option<String>,
+    },
+}
+
+pub(crate) async fn build_outputs(
+    configs: Vec<OutputConfig>,
+) -> Result<Vec<Box<dyn Output>>, Box<dyn Error>> {
+    let mut outputs: Vec<Box<dyn Output>> = Vec::new();
+    for config in configs {
+        let output: Box<dyn Output> = match config {
+            OutputConfig::Influxdb {
+                bucket,
+                measurement,
+                host,
+                org,
+                token,
+            } => Box::new(InfluxOutput::new(host, org, token, bucket, measurement)),
+            OutputConfig::Mqtt {
+                host,
+                port,
+                topic,
+                username,
+                password,
+            } => Box::new(MqttOutput::new(host, port, topic, username, password).await?),
+            OutputConfig::Json { path } => Box::new(JsonOutput::new(path)),
+        };
+        outputs.push(output);
+    }
+    Ok(outputs)
+}
+
+pub(crate) struct InfluxOutput {
+    client: influxdb2::Client,
+    bucket: String,
+    measurement: String,
+}
+
+impl InfluxOutput {
+    pub(crate) fn new(
+        host: String,
+        org: String,
+        token: String,
+        bucket: String,
+        measurement: String,
+    ) -> InfluxOutput {
+        InfluxOutput {
+            client: influxdb2::Client::new(host, org, token),
+            bucket,
+            measurement,
+        }
+    }
+}
+
+#[async_trait]
+impl Output for InfluxOutput {
+    async fn write(&self, data: &[RuuviData]) -> Result<(), Box<dyn Error>> {
+        use futures::stream;
+        use influxdb2::models::DataPoint;
+
+        let mut points: Vec<DataPoint> = Vec::new();
+        for point in data {
+            let mut builder = DataPoint::builder(self.measurement.clone())
+                .tag("name", point.name.clone())
+                .tag("mac", point.mac_address.to_string())
+                .field("temperature", point.temperature as f64)
+                .field("humidity", point.humidity as f64)
+                .field("pressure", point.pressure as i64)
+                .field("acceleration_x", point.acceleration_x as f64)
+                .field("acceleration_y", point.acceleration_y as f64)
+                .field("acceleration_z", point.acceleration_z as f64)
+                .field("voltage", point.voltage as f64);
+            if let Some(tx_power) = point.tx_power {
+                builder = builder.field("tx_power", tx_power as i64);
+            }
+            if let Some(movement_counter) = point.movement_counter {
+                builder = builder.field("movement_counter", movement_counter as i64);
+            }
+            if let Some(measurement_sequence) = point.measurement_sequence {
+                builder = builder.field("measurement_sequence", measurement_sequence as i64);
+            }
+            if let Some(rssi) = point.rssi {
+                builder = builder.field("rssi", rssi as i64);
+            }
+            builder = builder.field("age_ms", point.received_at.elapsed().as_millis() as i64);
+            points.push(builder.build()?);
+        }
+        self.client.write(&self.bucket, stream::iter(points)).await?;
+        Ok(())
+    }
+}
+
+pub(crate) struct MqttOutput {
+    client: rumqttc::AsyncClient,
+    topic: String,
+}
+
+impl MqttOutput {
+    pub(crate) async fn new(
+        host: String,
+        port: u16,
+        topic: String,
+        username: Option<String>,
+        password: Option<String>,
+    ) -> Result<MqttOutput, Box<dyn Error>> {
+        let mut options = rumqttc::MqttOptions::new("ruusti-tag", host, port);
+        if let (Some(username), Some(password)) = (username, password) {
+            options.set_credentials(username, password);
+        }
+        let (client, mut event_loop) = rumqttc::AsyncClient::new(options, 10);
+        tokio::spawn(async move {
+            loop {
+                if let Err(err) = event_loop.poll().await {
+                    eprintln!("MQTT event loop error: {}", err);
+                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                }
+            }
+        });
+        Ok(MqttOutput { client, topic })
+    }
+}
+
+#[async_trait]
+impl Output for MqttOutput {
+    async fn write(&self, data: &[RuuviData]) -> Result<(), Box<dyn Error>> {
+        for point in data {
+            let payload = serde_json::to_vec(point)?;
+            self.client
+                .publish(&self.topic, rumqttc::QoS::AtLeastOnce, false, payload)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct JsonOutput {
+    path: Option<String>,
+}
+
+impl JsonOutput {
+    pub(crate) fn new(path: Option<String>) -> JsonOutput {
+        JsonOutput { path }
+    }
+}
+
+#[async_trait]
+impl Output for JsonOutput {
+    async fn write(&self, data: &[RuuviData]) -> Result<(), Box<dyn Error>> {
+        let mut buf = String::new();
+        for point in data {
+            buf.push_str(&serde_json::to_string(point)?);
+            buf.push('\n');
+        }
+
+        match &self.path {
+            Some(path) => {
+                use tokio::io::AsyncWriteExt;
+                let mut file = tokio::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .await?;
+                file.write_all(buf.as_bytes()).await?;
+            }
+            None => print!("{}", buf),
+        }
+        Ok(())
+    }
+}